@@ -0,0 +1,317 @@
+// src/client/codec.rs
+//! Binary frame codec for the Bilibili live danmaku WebSocket protocol.
+//!
+//! Every frame on the wire carries one or more concatenated packets, each
+//! made up of a 16-byte big-endian header (see [`MsgHead`]) followed by a
+//! body. The body is either plain UTF-8 JSON (`ver == 0`), zlib/deflate
+//! compressed (`ver == 2`) or brotli compressed (`ver == 3`); once a
+//! compressed body is inflated it contains more concatenated packets that
+//! must be decoded recursively.
+
+use std::io::{self, Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use super::models::{BiliMessage, MsgHead};
+
+const OP_HEARTBEAT_REPLY: u32 = 3;
+const OP_SEND_MSG_REPLY: u32 = 5;
+const OP_AUTH_REPLY: u32 = 8;
+
+/// Upper bound on how much a single compressed packet may inflate to, so a
+/// malicious server can't force unbounded memory use with a small payload.
+const MAX_INFLATED_SIZE: usize = 16 * 1024 * 1024;
+/// Upper bound on how many compressed bodies may be nested inside one
+/// another, guarding against stack exhaustion from nested frames.
+const MAX_DECODE_DEPTH: u32 = 16;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CodecError {
+    #[error("io error while decoding frame: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid packet: pack_len {pack_len} is smaller than raw_header_size {raw_header_size}")]
+    HeaderTooSmall { pack_len: u32, raw_header_size: u16 },
+    #[error("invalid packet: pack_len {pack_len} overruns remaining buffer of {remaining} bytes")]
+    PacketOverrun { pack_len: u32, remaining: usize },
+    #[error("invalid packet: pack_len is zero, which would never advance past this packet")]
+    ZeroLengthPacket,
+    #[error("compressed body decoded to more than {limit} bytes")]
+    DecompressedTooLarge { limit: usize },
+    #[error("packets nested more than {limit} levels deep")]
+    RecursionLimitExceeded { limit: u32 },
+    #[error("malformed JSON body: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid UTF-8 body: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("unsupported protocol version: {0}")]
+    UnsupportedVersion(u16),
+}
+
+pub type Result<T> = std::result::Result<T, CodecError>;
+
+/// A type that can be read from and written to the big-endian binary wire
+/// format used by the danmaku protocol.
+pub trait Serializable: Sized {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self>;
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()>;
+}
+
+impl Serializable for MsgHead {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let pack_len = reader.read_u32::<BigEndian>()?;
+        let raw_header_size = reader.read_u16::<BigEndian>()?;
+        let ver = reader.read_u16::<BigEndian>()?;
+        let operation = reader.read_u32::<BigEndian>()?;
+        let seq_id = reader.read_u32::<BigEndian>()?;
+        Ok(MsgHead {
+            pack_len,
+            raw_header_size,
+            ver,
+            operation,
+            seq_id,
+        })
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<BigEndian>(self.pack_len)?;
+        writer.write_u16::<BigEndian>(self.raw_header_size)?;
+        writer.write_u16::<BigEndian>(self.ver)?;
+        writer.write_u32::<BigEndian>(self.operation)?;
+        writer.write_u32::<BigEndian>(self.seq_id)?;
+        Ok(())
+    }
+}
+
+/// Decode a raw WebSocket frame into zero or more [`BiliMessage`]s.
+///
+/// A single frame may carry several concatenated packets (this happens once
+/// a compressed body has been inflated), so this recurses over `buf` until
+/// it is fully consumed.
+pub fn decode_frame(buf: &[u8]) -> Result<Vec<BiliMessage>> {
+    let mut messages = Vec::new();
+    decode_packets(buf, &mut messages, 0)?;
+    Ok(messages)
+}
+
+fn decode_packets(buf: &[u8], out: &mut Vec<BiliMessage>, depth: u32) -> Result<()> {
+    if depth > MAX_DECODE_DEPTH {
+        return Err(CodecError::RecursionLimitExceeded {
+            limit: MAX_DECODE_DEPTH,
+        });
+    }
+
+    let mut offset = 0usize;
+    while offset < buf.len() {
+        let mut cursor = io::Cursor::new(&buf[offset..]);
+        let head = MsgHead::read_from(&mut cursor)?;
+
+        if head.pack_len == 0 {
+            return Err(CodecError::ZeroLengthPacket);
+        }
+
+        if head.pack_len < head.raw_header_size as u32 {
+            return Err(CodecError::HeaderTooSmall {
+                pack_len: head.pack_len,
+                raw_header_size: head.raw_header_size,
+            });
+        }
+
+        let pack_len = head.pack_len as usize;
+        let remaining = buf.len() - offset;
+        if pack_len > remaining {
+            return Err(CodecError::PacketOverrun {
+                pack_len: head.pack_len,
+                remaining,
+            });
+        }
+
+        let body_start = offset + head.raw_header_size as usize;
+        let body_end = offset + pack_len;
+        decode_body(&head, &buf[body_start..body_end], out, depth)?;
+
+        offset += pack_len;
+    }
+    Ok(())
+}
+
+fn decode_body(head: &MsgHead, body: &[u8], out: &mut Vec<BiliMessage>, depth: u32) -> Result<()> {
+    match head.ver {
+        0 => decode_operation(head.operation, body, out),
+        2 => decode_packets(&inflate_zlib(body)?, out, depth + 1),
+        3 => decode_packets(&inflate_brotli(body)?, out, depth + 1),
+        ver => Err(CodecError::UnsupportedVersion(ver)),
+    }
+}
+
+fn decode_operation(operation: u32, body: &[u8], out: &mut Vec<BiliMessage>) -> Result<()> {
+    match operation {
+        OP_HEARTBEAT_REPLY => {
+            let popularity = if body.len() >= 4 {
+                io::Cursor::new(body).read_u32::<BigEndian>()?
+            } else {
+                0
+            };
+            out.push(BiliMessage::Raw(serde_json::json!({ "popularity": popularity })));
+            Ok(())
+        }
+        OP_SEND_MSG_REPLY => {
+            let value: serde_json::Value = serde_json::from_str(std::str::from_utf8(body)?)?;
+            let cmd = value
+                .get("cmd")
+                .and_then(|c| c.as_str())
+                .unwrap_or_default()
+                .to_string();
+            out.push(BiliMessage::from_cmd(&cmd, value));
+            Ok(())
+        }
+        OP_AUTH_REPLY => {
+            let value: serde_json::Value = serde_json::from_str(std::str::from_utf8(body)?)?;
+            out.push(BiliMessage::Raw(value));
+            Ok(())
+        }
+        _ => {
+            let value: serde_json::Value = serde_json::from_str(std::str::from_utf8(body)?)?;
+            out.push(BiliMessage::Raw(value));
+            Ok(())
+        }
+    }
+}
+
+/// Read `reader` to the end, rejecting output past `MAX_INFLATED_SIZE` so a
+/// small compressed packet can't be used to exhaust memory.
+fn read_bounded<R: Read>(reader: R) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    reader
+        .take(MAX_INFLATED_SIZE as u64 + 1)
+        .read_to_end(&mut out)?;
+    if out.len() > MAX_INFLATED_SIZE {
+        return Err(CodecError::DecompressedTooLarge {
+            limit: MAX_INFLATED_SIZE,
+        });
+    }
+    Ok(out)
+}
+
+fn inflate_zlib(body: &[u8]) -> Result<Vec<u8>> {
+    use flate2::read::ZlibDecoder;
+
+    read_bounded(ZlibDecoder::new(body))
+}
+
+fn inflate_brotli(body: &[u8]) -> Result<Vec<u8>> {
+    read_bounded(brotli::Decompressor::new(body, 4096))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_packet(ver: u16, operation: u32, body: &[u8]) -> Vec<u8> {
+        let head = MsgHead {
+            pack_len: (16 + body.len()) as u32,
+            raw_header_size: 16,
+            ver,
+            operation,
+            seq_id: 1,
+        };
+        let mut buf = Vec::new();
+        head.write_to(&mut buf).unwrap();
+        buf.extend_from_slice(body);
+        buf
+    }
+
+    #[test]
+    fn decodes_plain_json_danmu() {
+        let body = br#"{"cmd":"DANMU_MSG","info":[[],"hello",[123,"alice"]]}"#;
+        let frame = encode_packet(0, OP_SEND_MSG_REPLY, body);
+        let messages = decode_frame(&frame).unwrap();
+        assert_eq!(messages.len(), 1);
+        match &messages[0] {
+            BiliMessage::Danmu { user, text } => {
+                assert_eq!(text, "hello");
+                assert_eq!(user.uid.unwrap().as_inner(), 123);
+                assert_eq!(user.base.name, "alice");
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decodes_heartbeat_reply() {
+        let mut body = Vec::new();
+        body.write_u32::<BigEndian>(4321).unwrap();
+        let frame = encode_packet(0, OP_HEARTBEAT_REPLY, &body);
+        let messages = decode_frame(&frame).unwrap();
+        assert_eq!(
+            messages[0],
+            BiliMessage::Raw(serde_json::json!({ "popularity": 4321 }))
+        );
+    }
+
+    #[test]
+    fn rejects_header_smaller_than_raw_header_size() {
+        let mut buf = Vec::new();
+        let head = MsgHead {
+            pack_len: 4,
+            raw_header_size: 16,
+            ver: 0,
+            operation: OP_SEND_MSG_REPLY,
+            seq_id: 1,
+        };
+        head.write_to(&mut buf).unwrap();
+        let err = decode_frame(&buf).unwrap_err();
+        assert!(matches!(err, CodecError::HeaderTooSmall { .. }));
+    }
+
+    #[test]
+    fn rejects_zero_length_packet() {
+        let buf = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0];
+        let err = decode_frame(&buf).unwrap_err();
+        assert!(matches!(err, CodecError::ZeroLengthPacket));
+    }
+
+    #[test]
+    fn rejects_packet_overrunning_buffer() {
+        let mut buf = Vec::new();
+        let head = MsgHead {
+            pack_len: 1000,
+            raw_header_size: 16,
+            ver: 0,
+            operation: OP_SEND_MSG_REPLY,
+            seq_id: 1,
+        };
+        head.write_to(&mut buf).unwrap();
+        let err = decode_frame(&buf).unwrap_err();
+        assert!(matches!(err, CodecError::PacketOverrun { .. }));
+    }
+
+    fn zlib_compress(data: &[u8]) -> Vec<u8> {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn rejects_decompressed_body_over_the_size_limit() {
+        let huge = vec![0u8; MAX_INFLATED_SIZE + 1];
+        let body = zlib_compress(&huge);
+        let frame = encode_packet(2, 0, &body);
+        let err = decode_frame(&frame).unwrap_err();
+        assert!(matches!(err, CodecError::DecompressedTooLarge { .. }));
+    }
+
+    #[test]
+    fn rejects_packets_nested_past_the_recursion_limit() {
+        // Each level wraps the previous packet in another zlib-compressed
+        // packet, simulating a server that nests compressed frames.
+        let mut inner = encode_packet(0, OP_HEARTBEAT_REPLY, &[]);
+        for _ in 0..=MAX_DECODE_DEPTH {
+            inner = encode_packet(2, 0, &zlib_compress(&inner));
+        }
+        let err = decode_frame(&inner).unwrap_err();
+        assert!(matches!(err, CodecError::RecursionLimitExceeded { .. }));
+    }
+}
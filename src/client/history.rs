@@ -0,0 +1,208 @@
+// src/client/history.rs
+//! Per-room ring buffers of recently decoded [`BiliMessage`]s, queryable by
+//! count or arrival time. Each room gets its own `RwLock<VecDeque>` so a
+//! read or write for one room never blocks another.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use super::ids::RoomId;
+use super::models::BiliMessage;
+
+/// A [`BiliMessage`] tagged with the instant it was recorded.
+#[derive(Debug, Clone)]
+pub struct TimestampedMessage {
+    pub message: BiliMessage,
+    pub received_at: Instant,
+}
+
+/// A capacity-bounded, optionally age-bounded ring buffer of the most
+/// recent messages for a single room.
+pub struct RoomHistory {
+    capacity: usize,
+    max_age: Option<Duration>,
+    messages: RwLock<VecDeque<TimestampedMessage>>,
+}
+
+impl RoomHistory {
+    pub fn new(capacity: usize, max_age: Option<Duration>) -> Self {
+        Self {
+            capacity,
+            max_age,
+            messages: RwLock::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Record a newly decoded message. Every entry older than `max_age` is
+    /// evicted first, then the oldest entry if the buffer is still at
+    /// capacity. A `capacity` of zero disables retention entirely.
+    pub fn push(&self, message: BiliMessage) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut buf = self.messages.write().unwrap();
+        self.evict_expired(&mut buf);
+        if buf.len() >= self.capacity {
+            buf.pop_front();
+        }
+        buf.push_back(TimestampedMessage {
+            message,
+            received_at: Instant::now(),
+        });
+    }
+
+    fn evict_expired(&self, buf: &mut VecDeque<TimestampedMessage>) {
+        let Some(max_age) = self.max_age else {
+            return;
+        };
+        let now = Instant::now();
+        while let Some(front) = buf.front() {
+            if now.duration_since(front.received_at) > max_age {
+                buf.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// The most recent `limit` messages, oldest first.
+    pub fn latest(&self, limit: usize) -> Vec<BiliMessage> {
+        let buf = self.messages.read().unwrap();
+        let skip = buf.len().saturating_sub(limit);
+        buf.iter().skip(skip).map(|m| m.message.clone()).collect()
+    }
+
+    /// All retained messages received at or after `instant`.
+    pub fn since(&self, instant: Instant) -> Vec<BiliMessage> {
+        let buf = self.messages.read().unwrap();
+        buf.iter()
+            .filter(|m| m.received_at >= instant)
+            .map(|m| m.message.clone())
+            .collect()
+    }
+
+    /// All retained messages received within `[start, end]`.
+    pub fn between(&self, start: Instant, end: Instant) -> Vec<BiliMessage> {
+        let buf = self.messages.read().unwrap();
+        buf.iter()
+            .filter(|m| m.received_at >= start && m.received_at <= end)
+            .map(|m| m.message.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Tracks one [`RoomHistory`] per room, created lazily on first use.
+pub struct HistoryStore {
+    capacity: usize,
+    max_age: Option<Duration>,
+    rooms: RwLock<HashMap<RoomId, Arc<RoomHistory>>>,
+}
+
+impl HistoryStore {
+    pub fn new(capacity: usize, max_age: Option<Duration>) -> Self {
+        Self {
+            capacity,
+            max_age,
+            rooms: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get (creating if necessary) the history buffer for `room_id`.
+    pub fn room(&self, room_id: RoomId) -> Arc<RoomHistory> {
+        if let Some(history) = self.rooms.read().unwrap().get(&room_id) {
+            return history.clone();
+        }
+        self.rooms
+            .write()
+            .unwrap()
+            .entry(room_id)
+            .or_insert_with(|| Arc::new(RoomHistory::new(self.capacity, self.max_age)))
+            .clone()
+    }
+
+    /// Record a message decoded for `room_id`.
+    pub fn record(&self, room_id: RoomId, message: BiliMessage) {
+        self.room(room_id).push(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn danmu(text: &str) -> BiliMessage {
+        BiliMessage::Danmu {
+            user: super::super::models::DanmuUser::new("tester"),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn latest_returns_most_recent_messages_in_order() {
+        let history = RoomHistory::new(2, None);
+        history.push(danmu("a"));
+        history.push(danmu("b"));
+        history.push(danmu("c"));
+
+        let latest = history.latest(2);
+        assert_eq!(latest, vec![danmu("b"), danmu("c")]);
+    }
+
+    #[test]
+    fn since_filters_by_arrival_time() {
+        let history = RoomHistory::new(10, None);
+        history.push(danmu("old"));
+        let cutoff = Instant::now();
+        history.push(danmu("new"));
+
+        let recent = history.since(cutoff);
+        assert_eq!(recent, vec![danmu("new")]);
+    }
+
+    #[test]
+    fn between_returns_only_messages_within_the_inclusive_window() {
+        let history = RoomHistory::new(10, None);
+        history.push(danmu("before"));
+        std::thread::sleep(Duration::from_millis(1));
+        let start = Instant::now();
+        history.push(danmu("in_window"));
+        std::thread::sleep(Duration::from_millis(1));
+        let end = Instant::now();
+        std::thread::sleep(Duration::from_millis(1));
+        history.push(danmu("after"));
+
+        let windowed = history.between(start, end);
+        assert_eq!(windowed, vec![danmu("in_window")]);
+    }
+
+    #[test]
+    fn zero_capacity_retains_nothing() {
+        let history = RoomHistory::new(0, None);
+        history.push(danmu("a"));
+        history.push(danmu("b"));
+
+        assert!(history.is_empty());
+        assert!(history.latest(10).is_empty());
+    }
+
+    #[test]
+    fn history_store_separates_rooms() {
+        let store = HistoryStore::new(10, None);
+        let room_a = RoomId::new(1).unwrap();
+        let room_b = RoomId::new(2).unwrap();
+
+        store.record(room_a, danmu("hello a"));
+
+        assert_eq!(store.room(room_a).len(), 1);
+        assert!(store.room(room_b).is_empty());
+    }
+}
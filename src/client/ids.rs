@@ -0,0 +1,117 @@
+// src/client/ids.rs
+//! Validated identifier newtypes for Bilibili room and user ids.
+//!
+//! Bare `u64`s threaded through the client make it easy to swap a room id
+//! for a uid by accident and give no defence against malformed input (e.g.
+//! `"0"` or a non-numeric string) beyond a panic at the call site. `RoomId`
+//! and `Uid` wrap the raw value behind a fallible constructor instead, so
+//! invalid ids become a typed [`IdError`] the caller can handle.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Bilibili never allocates room or user ids at or above this value.
+const MAX_ID: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IdError {
+    #[error("id must be non-zero")]
+    Zero,
+    #[error("id {0} is out of range (must be < {MAX_ID})")]
+    OutOfRange(u64),
+    #[error("id is not a valid number")]
+    NotANumber,
+}
+
+macro_rules! validated_id {
+    ($name:ident) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(u64);
+
+        impl $name {
+            /// Validate and wrap a raw id, rejecting zero and
+            /// out-of-range values.
+            pub fn new(value: u64) -> Result<Self, IdError> {
+                if value == 0 {
+                    return Err(IdError::Zero);
+                }
+                if value >= MAX_ID {
+                    return Err(IdError::OutOfRange(value));
+                }
+                Ok(Self(value))
+            }
+
+            pub fn as_inner(&self) -> u64 {
+                self.0
+            }
+
+            pub fn into_inner(self) -> u64 {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let value: u64 = s.parse().map_err(|_| IdError::NotANumber)?;
+                Self::new(value)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u64(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = u64::deserialize(deserializer)?;
+                Self::new(value).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+validated_id!(RoomId);
+validated_id!(Uid);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_zero() {
+        assert_eq!(RoomId::new(0), Err(IdError::Zero));
+        assert_eq!(Uid::new(0), Err(IdError::Zero));
+    }
+
+    #[test]
+    fn rejects_out_of_range() {
+        assert_eq!(RoomId::new(MAX_ID), Err(IdError::OutOfRange(MAX_ID)));
+    }
+
+    #[test]
+    fn accepts_and_round_trips_a_valid_id() {
+        let room = RoomId::new(12345).unwrap();
+        assert_eq!(room.as_inner(), 12345);
+        assert_eq!(room.into_inner(), 12345);
+        assert_eq!(room.to_string(), "12345");
+    }
+
+    #[test]
+    fn parses_from_str() {
+        assert_eq!("67890".parse::<Uid>().unwrap(), Uid::new(67890).unwrap());
+        assert_eq!("0".parse::<Uid>(), Err(IdError::Zero));
+        assert_eq!("not-a-number".parse::<Uid>(), Err(IdError::NotANumber));
+    }
+}
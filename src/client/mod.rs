@@ -0,0 +1,8 @@
+// src/client/mod.rs
+//! Client-side building blocks for connecting to Bilibili's live danmaku feed.
+
+pub mod codec;
+pub mod history;
+pub mod ids;
+pub mod models;
+pub mod room_info;
@@ -4,6 +4,8 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, fmt::Display};
 
+use super::ids::{IdError, RoomId, Uid};
+
 #[derive(Debug)]
 pub struct DanmuServer {
     pub host: String,
@@ -34,24 +36,86 @@ pub struct MsgHead {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthMessage {
-    pub uid: u64,
-    pub roomid: u64,
+    /// `None` for bilibili's anonymous/guest handshake, which is sent on
+    /// the wire as `uid: 0`.
+    #[serde(with = "guest_uid")]
+    pub uid: Option<Uid>,
+    pub roomid: RoomId,
     pub protover: i32,
     pub platform: String,
     pub type_: i32,
     pub key: String,
 }
 
+/// (De)serializes `Option<Uid>` as a plain `u64`, with `0` meaning guest
+/// (`None`) rather than being rejected like every other `Uid`.
+mod guest_uid {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{IdError, Uid};
+
+    pub fn serialize<S: Serializer>(uid: &Option<Uid>, serializer: S) -> Result<S::Ok, S::Error> {
+        uid.map(|u| u.as_inner()).unwrap_or(0).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Uid>, D::Error> {
+        let raw = u64::deserialize(deserializer)?;
+        match Uid::new(raw) {
+            Ok(uid) => Ok(Some(uid)),
+            Err(IdError::Zero) => Ok(None),
+            Err(err) => Err(D::Error::custom(err)),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthMessageError {
+    #[error("missing required field `{0}`")]
+    MissingField(&'static str),
+    #[error("invalid {field}: {source}")]
+    InvalidId {
+        field: &'static str,
+        #[source]
+        source: IdError,
+    },
+}
+
 impl AuthMessage {
-    pub fn from(map: &HashMap<String, String>) -> AuthMessage {
-        AuthMessage {
-            uid: map.get("uid").unwrap().parse::<u64>().unwrap(),
-            roomid: map.get("room_id").unwrap().parse::<u64>().unwrap(),
+    pub fn from(map: &HashMap<String, String>) -> Result<AuthMessage, AuthMessageError> {
+        let uid_raw = map
+            .get("uid")
+            .ok_or(AuthMessageError::MissingField("uid"))?
+            .parse::<u64>()
+            .map_err(|_| AuthMessageError::InvalidId {
+                field: "uid",
+                source: IdError::NotANumber,
+            })?;
+        // `0` is bilibili's anonymous/guest uid, not an invalid one.
+        let uid = if uid_raw == 0 {
+            None
+        } else {
+            let uid = Uid::new(uid_raw)
+                .map_err(|source| AuthMessageError::InvalidId { field: "uid", source })?;
+            Some(uid)
+        };
+        let roomid = map
+            .get("room_id")
+            .ok_or(AuthMessageError::MissingField("room_id"))?
+            .parse::<RoomId>()
+            .map_err(|source| AuthMessageError::InvalidId { field: "room_id", source })?;
+        let key = map
+            .get("token")
+            .ok_or(AuthMessageError::MissingField("token"))?
+            .to_string();
+
+        Ok(AuthMessage {
+            uid,
+            roomid,
             protover: 3,
             platform: "web".to_string(),
             type_: 2,
-            key: map.get("token").unwrap().to_string(),
-        }
+            key,
+        })
     }
 }
 
@@ -72,12 +136,180 @@ pub enum BiliMessage {
         /// Number of online users in the live room
         online_count: u64,
     },
+    /// Superchat purchase (SUPER_CHAT_MESSAGE)
+    SuperChat {
+        user: DanmuUser,
+        price: i64,
+        message: String,
+        duration: i64,
+    },
+    /// Guard (membership) purchase (GUARD_BUY)
+    GuardBuy {
+        user: DanmuUser,
+        guard_level: i64,
+        num: i64,
+    },
+    /// Room interaction, e.g. entering or following (INTERACT_WORD)
+    InteractWord {
+        user: DanmuUser,
+        action: InteractAction,
+    },
+    /// Updated watched-user count (WATCHED_CHANGE)
+    WatchedChange {
+        num: u64,
+    },
+    /// Updated like count (LIKE_INFO_V3_CLICK)
+    LikeInfo {
+        count: u64,
+    },
     // Add more variants as needed
     Raw(serde_json::Value),
     #[deprecated(note = "Use Raw variant instead")]
     Unsupported,
 }
 
+/// The kind of interaction carried by an `INTERACT_WORD` message.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InteractAction {
+    Enter,
+    Follow,
+    Share,
+}
+
+impl BiliMessage {
+    /// Build a [`BiliMessage`] from a decoded `cmd` field and its JSON
+    /// payload, falling back to [`BiliMessage::Raw`] for commands that have
+    /// no typed variant yet.
+    pub fn from_cmd(cmd: &str, value: serde_json::Value) -> BiliMessage {
+        match cmd {
+            "DANMU_MSG" => Self::parse_danmu(&value),
+            "SEND_GIFT" => Self::parse_gift(&value),
+            "ONLINE_RANK_COUNT" => Self::parse_online_rank_count(&value),
+            "SUPER_CHAT_MESSAGE" => Self::parse_super_chat(&value),
+            "GUARD_BUY" => Self::parse_guard_buy(&value),
+            "INTERACT_WORD" => Self::parse_interact_word(&value),
+            "WATCHED_CHANGE" => Self::parse_watched_change(&value),
+            "LIKE_INFO_V3_CLICK" => Self::parse_like_info(&value),
+            _ => None,
+        }
+        .unwrap_or(BiliMessage::Raw(value))
+    }
+
+    fn parse_danmu(value: &serde_json::Value) -> Option<BiliMessage> {
+        let info = value.get("info")?.as_array()?;
+        let text = info.get(1)?.as_str()?.to_string();
+        let user_info = info.get(2)?.as_array()?;
+        let uid = user_info.first()?.as_u64().and_then(|v| Uid::new(v).ok());
+        let name = user_info.get(1)?.as_str()?.to_string();
+        let medal = info
+            .get(3)
+            .and_then(|m| m.as_array())
+            .filter(|arr| !arr.is_empty())
+            .and_then(|arr| {
+                Some(Medal {
+                    level: arr.first()?.as_i64()?,
+                    name: arr.get(1)?.as_str()?.to_string(),
+                })
+            });
+
+        Some(BiliMessage::Danmu {
+            user: DanmuUser {
+                uid,
+                base: UserBase { name },
+                medal,
+            },
+            text,
+        })
+    }
+
+    fn parse_gift(value: &serde_json::Value) -> Option<BiliMessage> {
+        let gift: GiftData = serde_json::from_value(value.get("data")?.clone()).ok()?;
+        Some(BiliMessage::Gift {
+            user: gift.uname.clone(),
+            gift,
+        })
+    }
+
+    fn parse_online_rank_count(value: &serde_json::Value) -> Option<BiliMessage> {
+        let data = value.get("data")?;
+        Some(BiliMessage::OnlineRankCount {
+            count: data.get("count")?.as_u64()?,
+            online_count: data.get("online_count")?.as_u64()?,
+        })
+    }
+
+    fn parse_super_chat(value: &serde_json::Value) -> Option<BiliMessage> {
+        let data = value.get("data")?;
+        let user_info = data.get("user_info")?;
+        Some(BiliMessage::SuperChat {
+            user: DanmuUser {
+                uid: Self::parse_uid(data, "uid"),
+                base: UserBase {
+                    name: user_info.get("uname")?.as_str()?.to_string(),
+                },
+                medal: None,
+            },
+            price: data.get("price")?.as_i64()?,
+            message: data.get("message")?.as_str()?.to_string(),
+            duration: data.get("time")?.as_i64()?,
+        })
+    }
+
+    fn parse_guard_buy(value: &serde_json::Value) -> Option<BiliMessage> {
+        let data = value.get("data")?;
+        Some(BiliMessage::GuardBuy {
+            user: DanmuUser {
+                uid: Self::parse_uid(data, "uid"),
+                base: UserBase {
+                    name: data.get("username")?.as_str()?.to_string(),
+                },
+                medal: None,
+            },
+            guard_level: data.get("guard_level")?.as_i64()?,
+            num: data.get("num")?.as_i64()?,
+        })
+    }
+
+    fn parse_interact_word(value: &serde_json::Value) -> Option<BiliMessage> {
+        let data = value.get("data")?;
+        let action = match data.get("msg_type")?.as_i64()? {
+            1 => InteractAction::Enter,
+            2 => InteractAction::Follow,
+            3 => InteractAction::Share,
+            _ => return None,
+        };
+        Some(BiliMessage::InteractWord {
+            user: DanmuUser {
+                uid: Self::parse_uid(data, "uid"),
+                base: UserBase {
+                    name: data.get("uname")?.as_str()?.to_string(),
+                },
+                medal: None,
+            },
+            action,
+        })
+    }
+
+    fn parse_watched_change(value: &serde_json::Value) -> Option<BiliMessage> {
+        let data = value.get("data")?;
+        Some(BiliMessage::WatchedChange {
+            num: data.get("num")?.as_u64()?,
+        })
+    }
+
+    fn parse_like_info(value: &serde_json::Value) -> Option<BiliMessage> {
+        let data = value.get("data")?;
+        Some(BiliMessage::LikeInfo {
+            count: data.get("click_count")?.as_u64()?,
+        })
+    }
+
+    fn parse_uid(data: &serde_json::Value, key: &str) -> Option<Uid> {
+        data.get(key)?.as_u64().and_then(|v| Uid::new(v).ok())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum CoinType {
@@ -86,12 +318,12 @@ pub enum CoinType {
     Gold,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct GiftData {
     #[serde(rename = "giftName")]
     pub gift_name: String,
     pub uname: String,
-    pub uid: u64,
+    pub uid: Uid,
     pub num: i64,
     pub price: i64,
     pub coin_type: CoinType,
@@ -115,7 +347,8 @@ pub struct Medal {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct DanmuUser {
-    pub uid: u64,
+    /// `None` for messages that carry no (or an unvalidated) uid.
+    pub uid: Option<Uid>,
     pub base: UserBase,
     pub medal: Option<Medal>,
 }
@@ -123,7 +356,7 @@ pub struct DanmuUser {
 impl DanmuUser {
     pub fn new(name: &str) -> Self {
         DanmuUser {
-            uid: 0,
+            uid: None,
             base: UserBase {
                 name: name.to_string(),
             },
@@ -153,9 +386,115 @@ mod tests {
         map.insert("uid".to_string(), "12345".to_string());
         map.insert("room_id".to_string(), "67890".to_string());
         map.insert("token".to_string(), "test_token".to_string());
-        let auth = AuthMessage::from(&map);
-        assert_eq!(auth.uid, 12345);
-        assert_eq!(auth.roomid, 67890);
+        let auth = AuthMessage::from(&map).unwrap();
+        assert_eq!(auth.uid, Some(Uid::new(12345).unwrap()));
+        assert_eq!(auth.roomid, RoomId::new(67890).unwrap());
         assert_eq!(auth.key, "test_token");
     }
+
+    #[test]
+    fn auth_message_from_map_treats_zero_uid_as_anonymous() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("uid".to_string(), "0".to_string());
+        map.insert("room_id".to_string(), "67890".to_string());
+        map.insert("token".to_string(), "test_token".to_string());
+        let auth = AuthMessage::from(&map).unwrap();
+        assert_eq!(auth.uid, None);
+    }
+
+    #[test]
+    fn anonymous_auth_message_serializes_uid_as_zero() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("uid".to_string(), "0".to_string());
+        map.insert("room_id".to_string(), "67890".to_string());
+        map.insert("token".to_string(), "test_token".to_string());
+        let auth = AuthMessage::from(&map).unwrap();
+        let json = serde_json::to_value(&auth).unwrap();
+        assert_eq!(json["uid"], serde_json::json!(0));
+    }
+
+    #[test]
+    fn auth_message_from_map_rejects_out_of_range_uid() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("uid".to_string(), u64::MAX.to_string());
+        map.insert("room_id".to_string(), "67890".to_string());
+        map.insert("token".to_string(), "test_token".to_string());
+        assert!(matches!(
+            AuthMessage::from(&map),
+            Err(AuthMessageError::InvalidId { field: "uid", .. })
+        ));
+    }
+
+    #[test]
+    fn deserializing_out_of_range_uid_errors_instead_of_becoming_anonymous() {
+        let value = serde_json::json!({
+            "uid": u64::MAX,
+            "roomid": 67890,
+            "protover": 3,
+            "platform": "web",
+            "type_": 2,
+            "key": "test_token",
+        });
+        assert!(serde_json::from_value::<AuthMessage>(value).is_err());
+    }
+
+    #[test]
+    fn auth_message_from_map_reports_missing_field() {
+        let map = std::collections::HashMap::new();
+        assert!(matches!(
+            AuthMessage::from(&map),
+            Err(AuthMessageError::MissingField("uid"))
+        ));
+    }
+
+    #[test]
+    fn from_cmd_parses_super_chat() {
+        let value = serde_json::json!({
+            "cmd": "SUPER_CHAT_MESSAGE",
+            "data": {
+                "uid": 42,
+                "price": 100,
+                "message": "hi",
+                "time": 60,
+                "user_info": { "uname": "bob" },
+            }
+        });
+        let message = BiliMessage::from_cmd("SUPER_CHAT_MESSAGE", value);
+        assert_eq!(
+            message,
+            BiliMessage::SuperChat {
+                user: DanmuUser {
+                    uid: Some(Uid::new(42).unwrap()),
+                    base: UserBase { name: "bob".to_string() },
+                    medal: None,
+                },
+                price: 100,
+                message: "hi".to_string(),
+                duration: 60,
+            }
+        );
+    }
+
+    #[test]
+    fn from_cmd_parses_interact_word() {
+        let value = serde_json::json!({
+            "cmd": "INTERACT_WORD",
+            "data": { "uid": 7, "uname": "carol", "msg_type": 2 }
+        });
+        let message = BiliMessage::from_cmd("INTERACT_WORD", value);
+        match message {
+            BiliMessage::InteractWord { user, action } => {
+                assert_eq!(user.base.name, "carol");
+                assert_eq!(action, InteractAction::Follow);
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn from_cmd_falls_back_to_raw_for_unknown_commands() {
+        let value = serde_json::json!({ "cmd": "SOME_FUTURE_CMD", "data": {} });
+        let message = BiliMessage::from_cmd("SOME_FUTURE_CMD", value.clone());
+        assert_eq!(message, BiliMessage::Raw(value));
+    }
 }
@@ -0,0 +1,255 @@
+// src/client/room_info.rs
+//! REST client that resolves a room id into the credentials needed to open
+//! the danmaku websocket: the canonical room id, a rotating auth token, and
+//! the list of danmaku servers. With session cookies attached it also
+//! resolves the caller's own uid instead of connecting anonymously.
+
+use std::collections::HashMap;
+
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::models::{AuthMessage, AuthMessageError, DanmuServer};
+
+const ROOM_INIT_URL: &str = "https://api.live.bilibili.com/room/v1/Room/room_init";
+const DANMU_INFO_URL: &str =
+    "https://api.live.bilibili.com/xlive/web-room/v1/index/getDanmuInfo";
+const NAV_URL: &str = "https://api.bilibili.com/x/web-interface/nav";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoomInfoError {
+    #[error("request to bilibili failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("bilibili API returned error {code}: {message}")]
+    Api { code: i64, message: String },
+    #[error("could not build auth message: {0}")]
+    Auth(#[from] AuthMessageError),
+}
+
+pub type Result<T> = std::result::Result<T, RoomInfoError>;
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    code: i64,
+    message: String,
+    data: Option<T>,
+}
+
+impl<T> ApiResponse<T> {
+    fn into_data(self) -> Result<T> {
+        if self.code != 0 {
+            return Err(RoomInfoError::Api {
+                code: self.code,
+                message: self.message,
+            });
+        }
+        self.data.ok_or(RoomInfoError::Api {
+            code: self.code,
+            message: self.message,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RoomInitData {
+    room_id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DanmuInfoData {
+    token: String,
+    host_list: Vec<HostEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HostEntry {
+    host: String,
+    port: i32,
+    wss_port: i32,
+    ws_port: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct NavData {
+    mid: u64,
+}
+
+/// Resolves a room id, fetches a rotating auth token, and lists the
+/// available danmaku servers, optionally authenticated as a logged-in user
+/// via `buvid3`/`SESSDATA` cookies.
+pub struct RoomInfoClient {
+    http: Client,
+    buvid3: Option<String>,
+    sessdata: Option<String>,
+}
+
+impl RoomInfoClient {
+    pub fn new() -> Self {
+        Self {
+            http: Client::new(),
+            buvid3: None,
+            sessdata: None,
+        }
+    }
+
+    /// Attach session cookies so the resolved auth uses the logged-in
+    /// user's real uid instead of an anonymous one.
+    pub fn with_cookies(mut self, buvid3: impl Into<String>, sessdata: impl Into<String>) -> Self {
+        self.buvid3 = Some(buvid3.into());
+        self.sessdata = Some(sessdata.into());
+        self
+    }
+
+    fn cookie_header(&self) -> Option<String> {
+        match (&self.buvid3, &self.sessdata) {
+            (Some(b), Some(s)) => Some(format!("buvid3={b}; SESSDATA={s}")),
+            (Some(b), None) => Some(format!("buvid3={b}")),
+            (None, Some(s)) => Some(format!("SESSDATA={s}")),
+            (None, None) => None,
+        }
+    }
+
+    fn apply_cookies(&self, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.cookie_header() {
+            Some(cookie) => req.header("Cookie", cookie),
+            None => req,
+        }
+    }
+
+    /// Resolve a short or real room id to the canonical id used on the
+    /// websocket protocol.
+    pub async fn resolve_room_id(&self, room_id: u64) -> Result<u64> {
+        let req = self
+            .http
+            .get(ROOM_INIT_URL)
+            .query(&[("id", room_id.to_string())]);
+        let resp: ApiResponse<RoomInitData> = self.apply_cookies(req).send().await?.json().await?;
+        Ok(resp.into_data()?.room_id)
+    }
+
+    /// Fetch the rotating auth token and server list for a (real) room id.
+    pub async fn fetch_danmu_info(&self, room_id: u64) -> Result<(String, Vec<DanmuServer>)> {
+        let req = self
+            .http
+            .get(DANMU_INFO_URL)
+            .query(&[("id", room_id.to_string())]);
+        let resp: ApiResponse<DanmuInfoData> = self.apply_cookies(req).send().await?.json().await?;
+        let data = resp.into_data()?;
+        let servers = data
+            .host_list
+            .into_iter()
+            .map(|h| DanmuServer {
+                host: h.host,
+                port: h.port,
+                wss_port: h.wss_port,
+                ws_port: h.ws_port,
+            })
+            .collect();
+        Ok((data.token, servers))
+    }
+
+    /// Resolve the uid of the logged-in user from the attached session
+    /// cookies, or `None` if no cookies were attached.
+    pub async fn resolve_uid(&self) -> Result<Option<u64>> {
+        if self.cookie_header().is_none() {
+            return Ok(None);
+        }
+        let req = self.http.get(NAV_URL);
+        let resp: ApiResponse<NavData> = self.apply_cookies(req).send().await?.json().await?;
+        Ok(Some(resp.into_data()?.mid))
+    }
+
+    /// Resolve `room_id` and produce a ready-to-use [`AuthMessage`] together
+    /// with the servers to connect to. `uid` is used as a fallback for
+    /// anonymous connections; if session cookies are attached, the
+    /// logged-in user's real uid takes precedence.
+    pub async fn connect_info(&self, room_id: u64, uid: u64) -> Result<(AuthMessage, Vec<DanmuServer>)> {
+        let real_room_id = self.resolve_room_id(room_id).await?;
+        let (token, servers) = self.fetch_danmu_info(real_room_id).await?;
+        let uid = self.resolve_uid().await?.unwrap_or(uid);
+
+        let mut map = HashMap::new();
+        map.insert("uid".to_string(), uid.to_string());
+        map.insert("room_id".to_string(), real_room_id.to_string());
+        map.insert("token".to_string(), token);
+
+        Ok((AuthMessage::from(&map)?, servers))
+    }
+}
+
+impl Default for RoomInfoClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn into_data_returns_data_when_code_is_zero() {
+        let resp = ApiResponse {
+            code: 0,
+            message: "ok".to_string(),
+            data: Some(42u64),
+        };
+        assert_eq!(resp.into_data().unwrap(), 42);
+    }
+
+    #[test]
+    fn into_data_errors_on_nonzero_code() {
+        let resp: ApiResponse<u64> = ApiResponse {
+            code: -400,
+            message: "bad request".to_string(),
+            data: None,
+        };
+        let err = resp.into_data().unwrap_err();
+        assert!(matches!(err, RoomInfoError::Api { code: -400, .. }));
+    }
+
+    #[test]
+    fn into_data_errors_when_data_missing_despite_zero_code() {
+        let resp: ApiResponse<u64> = ApiResponse {
+            code: 0,
+            message: "ok".to_string(),
+            data: None,
+        };
+        assert!(matches!(
+            resp.into_data(),
+            Err(RoomInfoError::Api { code: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn cookie_header_is_none_without_cookies() {
+        assert_eq!(RoomInfoClient::new().cookie_header(), None);
+    }
+
+    #[test]
+    fn cookie_header_combines_both_cookies() {
+        let client = RoomInfoClient::new().with_cookies("buvid", "sess");
+        assert_eq!(
+            client.cookie_header(),
+            Some("buvid3=buvid; SESSDATA=sess".to_string())
+        );
+    }
+
+    #[test]
+    fn cookie_header_with_only_buvid3() {
+        let client = RoomInfoClient {
+            buvid3: Some("buvid".to_string()),
+            ..RoomInfoClient::new()
+        };
+        assert_eq!(client.cookie_header(), Some("buvid3=buvid".to_string()));
+    }
+
+    #[test]
+    fn cookie_header_with_only_sessdata() {
+        let client = RoomInfoClient {
+            sessdata: Some("sess".to_string()),
+            ..RoomInfoClient::new()
+        };
+        assert_eq!(client.cookie_header(), Some("SESSDATA=sess".to_string()));
+    }
+}
@@ -0,0 +1,4 @@
+// src/lib.rs
+//! blivedm_rs: a Rust client for Bilibili's live danmaku WebSocket protocol.
+
+pub mod client;